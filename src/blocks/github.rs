@@ -4,13 +4,15 @@ extern crate reqwest;
 
 use crossbeam_channel::Sender;
 use std::collections::HashMap;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use crate::block::{Block, ConfigBlock};
 use crate::config::Config;
 use crate::de::deserialize_duration;
 use crate::errors::*;
-use crate::input::I3BarEvent;
+use crate::input::{I3BarEvent, MouseButton};
 use crate::regex::Regex;
 use crate::scheduler::Task;
 use crate::util::FormatTemplate;
@@ -23,9 +25,40 @@ const GITHUB_TOKEN_ENV: &str = "GITHUB_TOKEN";
 pub struct Github {
     text: TextWidget,
     id: String,
-    update_interval: Duration,
-    gh: GithubClient,
-    format: FormatTemplate,
+    // Rendered by the background polling thread; `update()` just reads it.
+    rendered: Arc<Mutex<String>>,
+    gh: Arc<GithubClient>,
+    mark_as_read_button: MouseButton,
+    // `mark_as_read` only makes sense for the notifications view; other modes
+    // don't have an "unread count" to clear.
+    mode: GithubMode,
+    // Hands mark-as-read clicks off to the polling thread, which owns
+    // `last_aggregations`/etag state and can reconcile the optimistic "0".
+    commands: Sender<PollerCommand>,
+}
+
+enum PollerCommand {
+    MarkAsRead,
+}
+
+/// Which kind of github activity a block instance displays.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GithubMode {
+    /// Unread notifications, grouped by reason (the original behavior).
+    Notifications,
+    /// Open pull requests that request a review from the authenticated user.
+    ReviewRequests,
+    /// Open pull requests assigned to the authenticated user.
+    Assigned,
+    /// CI status of the configured `checks_repos`.
+    Checks,
+}
+
+impl Default for GithubMode {
+    fn default() -> Self {
+        GithubMode::Notifications
+    }
 }
 
 #[derive(Deserialize, Debug, Default, Clone)]
@@ -44,6 +77,38 @@ pub struct GithubConfig {
     /// Format override
     #[serde(default = "GithubConfig::default_format")]
     pub format: String,
+
+    /// Mouse button that marks all notifications as read
+    #[serde(default = "GithubConfig::default_mark_as_read_button")]
+    pub mark_as_read_button: MouseButton,
+
+    /// Only mark notifications updated before this timestamp as read (RFC 3339), passed
+    /// as `last_read_at` in the `PUT /notifications` body. Defaults to marking everything read.
+    #[serde(default)]
+    pub mark_as_read_since: Option<String>,
+
+    /// Read the token from this file instead of the `GITHUB_TOKEN` environment variable
+    #[serde(default)]
+    pub token_file: Option<String>,
+
+    /// Run this shell command and use its (trimmed) stdout as the token, e.g. `pass github/token`
+    #[serde(default)]
+    pub token_command: Option<String>,
+
+    /// Send the resolved token as `Authorization: Bearer <token>` instead of `token <token>`,
+    /// as required by fine-grained personal access tokens
+    #[serde(default)]
+    pub bearer_token: bool,
+
+    /// Which kind of github activity to display
+    #[serde(default)]
+    pub mode: GithubMode,
+
+    /// Repositories to query CI status for in `checks` mode, as `owner/repo` or
+    /// `owner/repo@ref`. When `@ref` is omitted, the repository's default branch is
+    /// resolved via the GitHub API and cached for subsequent polls.
+    #[serde(default)]
+    pub checks_repos: Vec<String>,
 }
 
 impl GithubConfig {
@@ -58,52 +123,375 @@ impl GithubConfig {
     fn default_format() -> String {
         "{total}".to_owned()
     }
+
+    fn default_mark_as_read_button() -> MouseButton {
+        MouseButton::Middle
+    }
+
+    // `{total}` (the block's default format) is meaningless in `checks` mode,
+    // which populates `{success}`/`{failure}`/`{pending}` instead; fall back
+    // to a mode-appropriate default rather than silently rendering "N/A".
+    fn default_format_for_mode(mode: GithubMode) -> &'static str {
+        match mode {
+            GithubMode::Checks => "{success}/{failure}/{pending}",
+            GithubMode::Notifications | GithubMode::ReviewRequests | GithubMode::Assigned => "{total}",
+        }
+    }
 }
 
 impl ConfigBlock for Github {
     type Config = GithubConfig;
 
-    fn new(block_config: Self::Config, config: Config, _: Sender<Task>) -> Result<Self> {
-        let token = match std::env::var(GITHUB_TOKEN_ENV).ok() {
-            Some(v) => v,
-            None => {
-                return Err(BlockError(
-                    "github".to_owned(),
-                    "missing GITHUB_TOKEN environment variable".to_owned(),
-                ))
-            }
+    fn new(mut block_config: Self::Config, config: Config, update_request: Sender<Task>) -> Result<Self> {
+        if block_config.format == GithubConfig::default_format() {
+            block_config.format = GithubConfig::default_format_for_mode(block_config.mode).to_owned();
+        }
+
+        let host = host_from_api_server(&block_config.api_server);
+        let token = resolve_token(&block_config, &host)?;
+        let credentials = if block_config.bearer_token {
+            Credentials::Bearer(token)
+        } else {
+            Credentials::Token(token)
         };
 
-        Ok(Github {
-            id: Uuid::new_v4().simple().to_string(),
+        let id = Uuid::new_v4().simple().to_string();
+        let gh = Arc::new(GithubClient::new(block_config.api_server, credentials));
+        let rendered = Arc::new(Mutex::new("N/A".to_owned()));
+        let (commands, commands_rx) = crossbeam_channel::unbounded();
+
+        let mut poller = Poller {
+            gh: gh.clone(),
+            mode: block_config.mode,
+            checks_repos: block_config.checks_repos,
             update_interval: block_config.interval,
-            text: TextWidget::new(config.clone()).with_text("N/A").with_icon("github"),
-            gh: GithubClient::new(block_config.api_server, token),
             format: FormatTemplate::from_string(&block_config.format)
                 .block_error("github", "Invalid format specified")?,
+            mark_as_read_since: block_config.mark_as_read_since,
+            last_aggregations: map!("total".to_owned() => 0),
+            rate_limited_until: None,
+            etag: None,
+            last_modified: None,
+            default_branches: HashMap::new(),
+        };
+        let poller_rendered = rendered.clone();
+        let poller_id = id.clone();
+
+        // Pagination can take several blocking HTTP round-trips, so it runs on
+        // its own thread and simply wakes the scheduler up whenever it has a
+        // fresh result, rather than running inline with `update()`. Clicks
+        // that need to mutate the poller's state (e.g. mark-as-read) go
+        // through `commands_rx` instead of touching `rendered` directly, so
+        // there's a single writer and a click can't be clobbered by an
+        // in-flight poll or vice versa.
+        thread::Builder::new()
+            .name("github".to_owned())
+            .spawn(move || loop {
+                let (text, sleep_for) = poller.poll();
+                *poller_rendered.lock().unwrap() = text;
+
+                if update_request
+                    .send(Task {
+                        id: poller_id.clone(),
+                        update_time: Instant::now(),
+                    })
+                    .is_err()
+                {
+                    return;
+                }
+
+                match commands_rx.recv_timeout(sleep_for) {
+                    Ok(PollerCommand::MarkAsRead) => {
+                        if let Some(text) = poller.mark_as_read() {
+                            *poller_rendered.lock().unwrap() = text;
+                            if update_request
+                                .send(Task {
+                                    id: poller_id.clone(),
+                                    update_time: Instant::now(),
+                                })
+                                .is_err()
+                            {
+                                return;
+                            }
+                        }
+                        // Loop straight back into `poll()` with no further
+                        // sleep, so the optimistic "0" above is reconciled
+                        // against the real state right away.
+                    }
+                    Err(crossbeam_channel::RecvTimeoutError::Timeout) => (),
+                    Err(crossbeam_channel::RecvTimeoutError::Disconnected) => return,
+                }
+            })
+            .block_error("github", "failed to spawn the polling thread")?;
+
+        Ok(Github {
+            id,
+            text: TextWidget::new(config.clone()).with_text("N/A").with_icon("github"),
+            rendered,
+            gh,
+            mark_as_read_button: block_config.mark_as_read_button,
+            mode: block_config.mode,
+            commands,
         })
     }
 }
 
 impl Block for Github {
     fn update(&mut self) -> Result<Option<Duration>> {
-        let aggregations = match self.gh.notifications().try_fold(
-            map!("total".to_owned() => 0),
-            |mut acc, notif| -> std::result::Result<HashMap<String, u64>, Box<dyn std::error::Error>> {
-                let n = notif?;
-                acc.entry(n.reason).and_modify(|v| *v += 1).or_insert(1);
-                acc.entry("total".to_owned()).and_modify(|v| *v += 1);
-                Ok(acc)
-            },
-        ) {
-            Ok(v) => v,
-            Err(_) => {
-                // If there is a error reported, set the value to N/A
-                self.text.set_text("N/A".to_owned());
-                return Ok(Some(self.update_interval));
+        self.text.set_text(self.rendered.lock().unwrap().clone());
+
+        Ok(None)
+    }
+
+    fn view(&self) -> Vec<&I3BarWidget> {
+        vec![&self.text]
+    }
+
+    fn click(&mut self, event: &I3BarEvent) -> Result<()> {
+        match event.button {
+            MouseButton::Left => {
+                self.gh.open_notifications()?;
             }
+            // Marking as read only makes sense in notifications mode; in the
+            // other modes there's no unread count to clear, so leave the
+            // configured button unbound.
+            button if button == self.mark_as_read_button && self.mode == GithubMode::Notifications => {
+                self.commands
+                    .send(PollerCommand::MarkAsRead)
+                    .block_error("github", "failed to reach the polling thread")?;
+            }
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+// Owns the polling loop's state (conditional-request cache, rate limit
+// backoff, last known aggregation) and runs entirely on the background
+// thread spawned by `ConfigBlock::new`.
+struct Poller {
+    gh: Arc<GithubClient>,
+    mode: GithubMode,
+    checks_repos: Vec<String>,
+    update_interval: Duration,
+    format: FormatTemplate,
+    mark_as_read_since: Option<String>,
+    last_aggregations: HashMap<String, u64>,
+    rate_limited_until: Option<SystemTime>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    // Resolved default branch per `owner/repo` entered without an explicit `@ref`,
+    // so we only hit `GET /repos/{owner}/{repo}` once per repo instead of every poll.
+    default_branches: HashMap<String, String>,
+}
+
+impl Poller {
+    // Resolves (and caches) the default branch for a `checks_repos` entry with no
+    // explicit `@ref`. `check-runs` only accepts a SHA/branch/tag, not `HEAD`.
+    fn default_branch(&mut self, repo: &str) -> Result<String> {
+        if let Some(git_ref) = self.default_branches.get(repo) {
+            return Ok(git_ref.clone());
+        }
+
+        let git_ref = self.gh.default_branch(repo)?;
+        self.default_branches.insert(repo.to_owned(), git_ref.clone());
+
+        Ok(git_ref)
+    }
+
+    fn poll(&mut self) -> (String, Duration) {
+        let now = SystemTime::now();
+
+        if let Some(reset_at) = self.rate_limited_until {
+            if now < reset_at {
+                return ("rate limited".to_owned(), reset_at.duration_since(now).unwrap_or(self.update_interval));
+            }
+            self.rate_limited_until = None;
+        }
+
+        match self.mode {
+            GithubMode::Notifications => self.poll_notifications(now),
+            GithubMode::ReviewRequests => self.poll_search(now, "is:open is:pr review-requested:@me"),
+            GithubMode::Assigned => self.poll_search(now, "is:open is:pr assignee:@me"),
+            GithubMode::Checks => self.poll_checks(now),
+        }
+    }
+
+    // Handles a `PollerCommand::MarkAsRead`. Returns the optimistic "0" text
+    // to render immediately; the next `poll()` (run right after, with no
+    // sleep in between) reconciles it against the real state.
+    fn mark_as_read(&mut self) -> Option<String> {
+        // Render a visible failure state on error instead of silently leaving
+        // the stale unread count up, same as every poll failure path below.
+        if self.gh.mark_as_read(self.mark_as_read_since.as_deref()).is_err() {
+            return Some("N/A".to_owned());
+        }
+
+        if self.mode != GithubMode::Notifications {
+            return None;
+        }
+
+        self.last_aggregations = map!("total".to_owned() => 0);
+        let aggregations = self.last_aggregations.clone();
+        Some(self.render(&aggregations))
+    }
+
+    // Checks `pages` for a rate limit hit, remembering when to resume.
+    // Returns the rendered state and backoff interval if so.
+    fn handle_rate_limit<T>(&mut self, now: SystemTime, pages: &Paginated<T>) -> Option<(String, Duration)> {
+        if pages.rate_limited {
+            let reset_at = pages
+                .retry_after
+                .map(|d| now + d)
+                .or_else(|| pages.rate_limit_reset.map(|secs| UNIX_EPOCH + Duration::from_secs(secs)))
+                .unwrap_or(now + self.update_interval);
+
+            self.rate_limited_until = Some(reset_at);
+            return Some(("rate limited".to_owned(), reset_at.duration_since(now).unwrap_or(self.update_interval)));
+        }
+
+        if pages.rate_limit_remaining == Some(0) {
+            if let Some(secs) = pages.rate_limit_reset {
+                self.rate_limited_until = Some(UNIX_EPOCH + Duration::from_secs(secs));
+            }
+        }
+
+        None
+    }
+
+    fn poll_notifications(&mut self, now: SystemTime) -> (String, Duration) {
+        let mut notifications = self.gh.notifications(self.etag.clone(), self.last_modified.clone());
+        let mut aggregations = map!("total".to_owned() => 0);
+        let mut poll_failed = false;
+
+        loop {
+            match notifications.try_next() {
+                Ok(Some(notif)) => {
+                    aggregations.entry(notif.reason).and_modify(|v| *v += 1).or_insert(1);
+                    aggregations.entry("total".to_owned()).and_modify(|v| *v += 1);
+                }
+                Ok(None) => break,
+                Err(_) => {
+                    poll_failed = true;
+                    break;
+                }
+            }
+        }
+
+        let not_modified = notifications.not_modified;
+        let poll_interval = notifications.poll_interval;
+
+        if let Some(etag) = notifications.new_etag.take() {
+            self.etag = Some(etag);
+        }
+        if let Some(last_modified) = notifications.new_last_modified.take() {
+            self.last_modified = Some(last_modified);
+        }
+
+        if poll_failed {
+            // If there is a error reported, set the value to N/A
+            return ("N/A".to_owned(), self.update_interval);
+        }
+
+        if let Some(result) = self.handle_rate_limit(now, &notifications) {
+            return result;
+        }
+
+        let aggregations = if not_modified {
+            self.last_aggregations.clone()
+        } else {
+            self.last_aggregations = aggregations.clone();
+            aggregations
+        };
+
+        let interval = match poll_interval {
+            Some(suggested) if suggested > self.update_interval => suggested,
+            _ => self.update_interval,
         };
 
+        (self.render(&aggregations), interval)
+    }
+
+    fn poll_search(&mut self, now: SystemTime, query: &str) -> (String, Duration) {
+        let mut results = self.gh.search(query);
+        let mut total: u64 = 0;
+        let mut poll_failed = false;
+
+        loop {
+            match results.try_next() {
+                Ok(Some(_)) => total += 1,
+                Ok(None) => break,
+                Err(_) => {
+                    poll_failed = true;
+                    break;
+                }
+            }
+        }
+
+        if poll_failed {
+            return ("N/A".to_owned(), self.update_interval);
+        }
+
+        if let Some(result) = self.handle_rate_limit(now, &results) {
+            return result;
+        }
+
+        let values = map!("{total}" => format!("{}", total));
+        let text = self.format.render_static_str(&values).unwrap_or_else(|_| "N/A".to_owned());
+
+        (text, self.update_interval)
+    }
+
+    fn poll_checks(&mut self, now: SystemTime) -> (String, Duration) {
+        let mut counts = map!("success" => 0u64, "failure" => 0u64, "pending" => 0u64);
+
+        for repo in self.checks_repos.clone() {
+            let (repo, git_ref) = match repo.split_once('@') {
+                Some((repo, git_ref)) => (repo.to_owned(), git_ref.to_owned()),
+                None => match self.default_branch(&repo) {
+                    Ok(git_ref) => (repo, git_ref),
+                    Err(_) => return ("N/A".to_owned(), self.update_interval),
+                },
+            };
+
+            let mut runs = self.gh.check_runs(&repo, &git_ref);
+
+            loop {
+                match runs.try_next() {
+                    Ok(Some(run)) => {
+                        let key = match run.conclusion.as_deref() {
+                            Some("success") => "success",
+                            Some(_) => "failure",
+                            None => "pending",
+                        };
+                        counts.entry(key).and_modify(|v| *v += 1);
+                    }
+                    Ok(None) => break,
+                    Err(_) => return ("N/A".to_owned(), self.update_interval),
+                }
+            }
+
+            if let Some(result) = self.handle_rate_limit(now, &runs) {
+                return result;
+            }
+        }
+
+        let values = map!(
+            "{success}" => format!("{}", counts["success"]),
+            "{failure}" => format!("{}", counts["failure"]),
+            "{pending}" => format!("{}", counts["pending"])
+        );
+        let text = self.format.render_static_str(&values).unwrap_or_else(|_| "N/A".to_owned());
+
+        (text, self.update_interval)
+    }
+
+    fn render(&self, aggregations: &HashMap<String, u64>) -> String {
         let default: u64 = 0;
         let values = map!(
             "{total}" => format!("{}", aggregations.get("total").unwrap_or(&default)),
@@ -122,86 +510,433 @@ impl Block for Github {
             "{team_mention}" => format!("{}", aggregations.get("team_mention").unwrap_or(&default))
         );
 
-        self.text.set_text(self.format.render_static_str(&values)?);
+        self.format.render_static_str(&values).unwrap_or_else(|_| "N/A".to_owned())
+    }
+}
+
+// Modeled after hubcaps' `Credentials`: a classic PAT is sent as
+// `Authorization: token <...>`, while fine-grained PATs and GitHub Apps
+// tokens expect `Authorization: Bearer <...>`.
+#[derive(Debug, Clone)]
+enum Credentials {
+    Token(String),
+    Bearer(String),
+}
 
-        Ok(Some(self.update_interval))
+impl Credentials {
+    fn header_value(&self) -> String {
+        match self {
+            Credentials::Token(token) => format!("token {}", token),
+            Credentials::Bearer(token) => format!("Bearer {}", token),
+        }
     }
+}
 
-    fn view(&self) -> Vec<&I3BarWidget> {
-        vec![&self.text]
+// The API lives at `api.github.com` (or `<host>/api/v3` on Enterprise), the
+// token stores in `~/.netrc` and `gh`'s `hosts.yml` are keyed by the plain host.
+fn host_from_api_server(api_server: &str) -> String {
+    api_server
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("api.")
+        .trim_end_matches("/api/v3")
+        .to_owned()
+}
+
+fn run_token_command(command: &str) -> std::result::Result<String, ()> {
+    let output = std::process::Command::new("sh").arg("-c").arg(command).output().map_err(|_| ())?;
+
+    if !output.status.success() {
+        return Err(());
     }
 
-    fn click(&mut self, _: &I3BarEvent) -> Result<()> {
-        Ok(())
+    String::from_utf8(output.stdout).map(|s| s.trim().to_owned()).map_err(|_| ())
+}
+
+// Minimal `~/.netrc` lookup: find the `password` that follows a `machine <host>` entry.
+fn netrc_password(host: &str) -> Option<String> {
+    let home = std::env::var("HOME").ok()?;
+    let content = std::fs::read_to_string(std::path::Path::new(&home).join(".netrc")).ok()?;
+
+    let mut tokens = content.split_whitespace();
+    let mut current_machine: Option<&str> = None;
+
+    while let Some(tok) = tokens.next() {
+        match tok {
+            "machine" => current_machine = tokens.next(),
+            "password" if current_machine == Some(host) => return tokens.next().map(|s| s.to_owned()),
+            _ => (),
+        }
     }
 
-    fn id(&self) -> &str {
-        &self.id
+    None
+}
+
+// Minimal `hosts.yml` lookup, as written by the `gh` CLI to
+// `~/.config/gh/hosts.yml`, e.g.:
+//   github.com:
+//       oauth_token: gho_xxx
+fn gh_cli_token(host: &str) -> Option<String> {
+    let home = std::env::var("HOME").ok()?;
+    let content = std::fs::read_to_string(std::path::Path::new(&home).join(".config/gh/hosts.yml")).ok()?;
+
+    let mut in_host_block = false;
+
+    for line in content.lines() {
+        if !line.starts_with(' ') && !line.starts_with('\t') {
+            in_host_block = line.trim_end_matches(':') == host;
+            continue;
+        }
+
+        if in_host_block {
+            if let Some(token) = line.trim().strip_prefix("oauth_token:") {
+                return Some(token.trim().trim_matches('"').to_owned());
+            }
+        }
     }
+
+    None
+}
+
+fn resolve_token(block_config: &GithubConfig, host: &str) -> Result<String> {
+    let mut tried = Vec::new();
+
+    if let Some(command) = &block_config.token_command {
+        tried.push("token_command");
+        if let Ok(token) = run_token_command(command) {
+            return Ok(token);
+        }
+    }
+
+    if let Some(path) = &block_config.token_file {
+        tried.push("token_file");
+        if let Ok(token) = std::fs::read_to_string(path) {
+            return Ok(token.trim().to_owned());
+        }
+    }
+
+    tried.push(GITHUB_TOKEN_ENV);
+    if let Ok(token) = std::env::var(GITHUB_TOKEN_ENV) {
+        return Ok(token);
+    }
+
+    tried.push("~/.netrc");
+    if let Some(token) = netrc_password(host) {
+        return Ok(token);
+    }
+
+    tried.push("gh CLI hosts.yml");
+    if let Some(token) = gh_cli_token(host) {
+        return Ok(token);
+    }
+
+    Err(BlockError(
+        "github".to_owned(),
+        format!("could not find a github token, tried: {}", tried.join(", ")),
+    ))
 }
 
 struct GithubClient {
     http: reqwest::Client,
     api_server: String,
-    token: String,
+    credentials: Credentials,
 }
 
 impl GithubClient {
-    fn new(api_server: String, token: String) -> Self {
+    fn new(api_server: String, credentials: Credentials) -> Self {
         GithubClient {
             http: reqwest::Client::builder()
                 .timeout(Duration::from_secs(5))
                 .build()
                 .unwrap(),
             api_server: api_server,
-            token: token,
+            credentials: credentials,
+        }
+    }
+
+    // The URL of the notifications inbox on the web UI, e.g.
+    // `https://api.github.com` -> `https://github.com/notifications`, or
+    // `https://ghe.example.com/api/v3` -> `https://ghe.example.com/notifications`
+    // for GitHub Enterprise.
+    fn notifications_web_url(&self) -> String {
+        let web_host = self
+            .api_server
+            .trim_end_matches("/api/v3")
+            .replace("//api.", "//");
+
+        format!("{}/notifications", web_host)
+    }
+
+    fn open_notifications(&self) -> Result<()> {
+        std::process::Command::new("xdg-open")
+            .arg(self.notifications_web_url())
+            .spawn()
+            .block_error("github", "failed to open the browser")?;
+
+        Ok(())
+    }
+
+    // `last_read_at` is an RFC 3339 timestamp: only notifications updated before it are
+    // marked as read. When absent, GitHub marks everything as read.
+    fn mark_as_read(&self, last_read_at: Option<&str>) -> Result<()> {
+        let mut request = self
+            .http
+            .put(&format!("{}/notifications", self.api_server))
+            .header(reqwest::header::AUTHORIZATION, self.credentials.header_value());
+
+        request = match last_read_at {
+            Some(last_read_at) => request
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(format!(r#"{{"last_read_at":"{}"}}"#, last_read_at)),
+            None => request.header(reqwest::header::CONTENT_LENGTH, 0),
+        };
+
+        let response = request.send().block_error("github", "failed to mark notifications as read")?;
+
+        if !response.status().is_success() {
+            return Err(BlockError(
+                "github".to_owned(),
+                "failed to mark notifications as read".to_owned(),
+            ));
         }
+
+        Ok(())
     }
 
-    fn notifications(&self) -> Notifications {
-        Notifications {
+    fn notifications(&self, if_none_match: Option<String>, if_modified_since: Option<String>) -> Paginated<Notification> {
+        Paginated {
             http: &self.http,
             next_page_url: format!("{}/notifications", self.api_server),
-            token: &self.token,
-            notifications: vec![].into_iter(),
+            auth_header: self.credentials.header_value(),
+            parse_page: parse_array_page::<Notification>,
+            items: vec![].into_iter(),
+            first_page: true,
+            if_none_match,
+            if_modified_since,
+            not_modified: false,
+            new_etag: None,
+            new_last_modified: None,
+            poll_interval: None,
+            rate_limited: false,
+            retry_after: None,
+            rate_limit_remaining: None,
+            rate_limit_reset: None,
+        }
+    }
+
+    fn search(&self, query: &str) -> Paginated<SearchIssue> {
+        Paginated {
+            http: &self.http,
+            next_page_url: format!("{}/search/issues?q={}", self.api_server, query.replace(' ', "+")),
+            auth_header: self.credentials.header_value(),
+            parse_page: parse_search_page,
+            items: vec![].into_iter(),
+            first_page: true,
+            if_none_match: None,
+            if_modified_since: None,
+            not_modified: false,
+            new_etag: None,
+            new_last_modified: None,
+            poll_interval: None,
+            rate_limited: false,
+            retry_after: None,
+            rate_limit_remaining: None,
+            rate_limit_reset: None,
         }
     }
+
+    fn check_runs(&self, repo: &str, git_ref: &str) -> Paginated<CheckRun> {
+        Paginated {
+            http: &self.http,
+            next_page_url: format!("{}/repos/{}/commits/{}/check-runs", self.api_server, repo, git_ref),
+            auth_header: self.credentials.header_value(),
+            parse_page: parse_check_runs_page,
+            items: vec![].into_iter(),
+            first_page: true,
+            if_none_match: None,
+            if_modified_since: None,
+            not_modified: false,
+            new_etag: None,
+            new_last_modified: None,
+            poll_interval: None,
+            rate_limited: false,
+            retry_after: None,
+            rate_limit_remaining: None,
+            rate_limit_reset: None,
+        }
+    }
+
+    fn default_branch(&self, repo: &str) -> Result<String> {
+        #[derive(Deserialize)]
+        struct Repo {
+            default_branch: String,
+        }
+
+        let mut response = self
+            .http
+            .get(&format!("{}/repos/{}", self.api_server, repo))
+            .header(reqwest::header::AUTHORIZATION, self.credentials.header_value())
+            .send()
+            .block_error("github", "failed to resolve the default branch")?;
+
+        if !response.status().is_success() {
+            return Err(BlockError(
+                "github".to_owned(),
+                format!("failed to resolve the default branch for {}", repo),
+            ));
+        }
+
+        response
+            .json::<Repo>()
+            .block_error("github", "failed to parse the repository response")
+            .map(|r| r.default_branch)
+    }
+}
+
+type PageParser<T> = fn(reqwest::Response) -> std::result::Result<Vec<T>, Box<dyn std::error::Error>>;
+
+fn parse_array_page<T: serde::de::DeserializeOwned>(
+    mut response: reqwest::Response,
+) -> std::result::Result<Vec<T>, Box<dyn std::error::Error>> {
+    Ok(response.json::<Vec<T>>()?)
+}
+
+fn parse_search_page(
+    mut response: reqwest::Response,
+) -> std::result::Result<Vec<SearchIssue>, Box<dyn std::error::Error>> {
+    #[derive(Deserialize)]
+    struct SearchResults {
+        items: Vec<SearchIssue>,
+    }
+
+    Ok(response.json::<SearchResults>()?.items)
 }
 
-struct Notifications<'a> {
-    notifications: <Vec<Notification> as IntoIterator>::IntoIter,
+fn parse_check_runs_page(
+    mut response: reqwest::Response,
+) -> std::result::Result<Vec<CheckRun>, Box<dyn std::error::Error>> {
+    #[derive(Deserialize)]
+    struct CheckRunsResponse {
+        check_runs: Vec<CheckRun>,
+    }
+
+    Ok(response.json::<CheckRunsResponse>()?.check_runs)
+}
+
+// Iterates over the results of any of GitHub's paginated (`Link` header)
+// endpoints, fetching each page lazily as the iterator is drained. Also tracks the
+// conditional-request and rate-limit bookkeeping shared by every mode.
+struct Paginated<'a, T> {
+    items: <Vec<T> as IntoIterator>::IntoIter,
     http: &'a reqwest::Client,
-    token: &'a str,
+    auth_header: String,
     next_page_url: String,
+    parse_page: PageParser<T>,
+    // Only sent on the very first page of a poll: conditional requests only
+    // make sense against the start of the result set.
+    first_page: bool,
+    if_none_match: Option<String>,
+    if_modified_since: Option<String>,
+    // Set once GitHub answers the first page with a 304, at which point the
+    // caller should keep displaying whatever it rendered last poll.
+    not_modified: bool,
+    new_etag: Option<String>,
+    new_last_modified: Option<String>,
+    // Server-suggested minimum interval until the next poll, from `X-Poll-Interval`.
+    poll_interval: Option<Duration>,
+    // Set on a `429` response, or a `403` carrying `Retry-After` or an exhausted
+    // `X-RateLimit-Remaining`, meaning the token is out of requests.
+    rate_limited: bool,
+    retry_after: Option<Duration>,
+    rate_limit_remaining: Option<u64>,
+    rate_limit_reset: Option<u64>,
 }
 
-impl<'a> Iterator for Notifications<'a> {
-    type Item = std::result::Result<Notification, Box<dyn std::error::Error>>;
+impl<'a, T> Iterator for Paginated<'a, T> {
+    type Item = std::result::Result<T, Box<dyn std::error::Error>>;
 
     fn next(&mut self) -> Option<Self::Item> {
         match self.try_next() {
-            Ok(Some(notif)) => Some(Ok(notif)),
+            Ok(Some(item)) => Some(Ok(item)),
             Ok(None) => None,
             Err(err) => Some(Err(err)),
         }
     }
 }
 
-impl<'a> Notifications<'a> {
-    fn try_next(&mut self) -> std::result::Result<Option<Notification>, Box<dyn std::error::Error>> {
-        if let Some(notif) = self.notifications.next() {
-            return Ok(Some(notif));
+impl<'a, T> Paginated<'a, T> {
+    fn try_next(&mut self) -> std::result::Result<Option<T>, Box<dyn std::error::Error>> {
+        if let Some(item) = self.items.next() {
+            return Ok(Some(item));
         }
 
-        if self.next_page_url == "" {
+        if self.rate_limited || self.not_modified || self.next_page_url == "" {
             return Ok(None);
         }
 
-        let mut response = self
+        let mut request = self
             .http
             .get(&self.next_page_url)
-            .header(reqwest::header::AUTHORIZATION, format!("token {}", self.token))
-            .send()?;
+            .header(reqwest::header::AUTHORIZATION, &self.auth_header);
+
+        if self.first_page {
+            if let Some(etag) = &self.if_none_match {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag.as_str());
+            }
+            if let Some(last_modified) = &self.if_modified_since {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified.as_str());
+            }
+        }
+
+        let mut response = request.send()?;
+
+        if self.first_page {
+            if let Some(v) = response.headers().get(reqwest::header::ETAG) {
+                self.new_etag = Some(v.to_str()?.to_owned());
+            }
+            if let Some(v) = response.headers().get(reqwest::header::LAST_MODIFIED) {
+                self.new_last_modified = Some(v.to_str()?.to_owned());
+            }
+            if let Some(v) = response.headers().get("x-poll-interval") {
+                if let Ok(secs) = v.to_str()?.parse::<u64>() {
+                    self.poll_interval = Some(Duration::from_secs(secs));
+                }
+            }
+            self.first_page = false;
+        }
+
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            // Conditional requests don't count against the rate limit, so
+            // this is the expected steady-state response between changes.
+            self.not_modified = true;
+            return Ok(None);
+        }
+
+        if let Some(v) = response.headers().get("x-ratelimit-remaining") {
+            self.rate_limit_remaining = v.to_str().ok().and_then(|s| s.parse::<u64>().ok());
+        }
+        if let Some(v) = response.headers().get("x-ratelimit-reset") {
+            self.rate_limit_reset = v.to_str().ok().and_then(|s| s.parse::<u64>().ok());
+        }
+
+        let retry_after = match response.headers().get(reqwest::header::RETRY_AFTER) {
+            Some(v) => v.to_str()?.parse::<u64>().ok().map(Duration::from_secs),
+            None => None,
+        };
+
+        // `429` is always a rate limit. `403` can also mean a bad/revoked token,
+        // missing scope, or an abuse-detection block, none of which carry a
+        // `Retry-After` or an exhausted `X-RateLimit-Remaining` - treat those as
+        // regular errors instead of backing off forever on an unrelated failure.
+        let is_rate_limited = response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS
+            || (response.status() == reqwest::StatusCode::FORBIDDEN
+                && (retry_after.is_some() || self.rate_limit_remaining == Some(0)));
+
+        if is_rate_limited {
+            self.rate_limited = true;
+            self.retry_after = retry_after;
+            return Ok(None);
+        }
 
         if !response.status().is_success() {
             return Err(Box::new(response.json::<GithubError>()?));
@@ -217,9 +952,9 @@ impl<'a> Notifications<'a> {
             None => "".to_owned(),
         };
 
-        self.notifications = response.json::<Vec<Notification>>()?.into_iter();
+        self.items = (self.parse_page)(response)?.into_iter();
 
-        Ok(self.notifications.next())
+        Ok(self.items.next())
     }
 }
 
@@ -228,6 +963,14 @@ struct Notification {
     reason: String,
 }
 
+#[derive(Deserialize)]
+struct SearchIssue {}
+
+#[derive(Deserialize)]
+struct CheckRun {
+    conclusion: Option<String>,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 struct GithubError {
     message: String,
@@ -264,3 +1007,115 @@ fn extract_links(raw_links: &str) -> HashMap<&str, &str> {
             }
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    // `netrc_password`/`gh_cli_token`/`resolve_token` read `$HOME` and env
+    // vars, so serialize the tests that touch them instead of racing on
+    // shared process state.
+    static HOME_LOCK: Mutex<()> = Mutex::new(());
+    static HOME_DIR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    fn test_home() -> std::path::PathBuf {
+        let n = HOME_DIR_COUNTER.fetch_add(1, Ordering::SeqCst);
+        let dir = std::env::temp_dir().join(format!("i3status-rust-github-test-{}-{}", std::process::id(), n));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn host_from_api_server_strips_scheme_and_api_prefix() {
+        assert_eq!(host_from_api_server("https://api.github.com"), "github.com");
+    }
+
+    #[test]
+    fn host_from_api_server_handles_enterprise_server() {
+        assert_eq!(
+            host_from_api_server("https://ghe.example.com/api/v3"),
+            "ghe.example.com"
+        );
+    }
+
+    #[test]
+    fn netrc_password_finds_entry_for_host() {
+        let _guard = HOME_LOCK.lock().unwrap();
+        let home = test_home();
+        std::fs::write(
+            home.join(".netrc"),
+            "machine github.com login me password abc123\nmachine other.com login x password def456\n",
+        )
+        .unwrap();
+        std::env::set_var("HOME", &home);
+
+        assert_eq!(netrc_password("github.com"), Some("abc123".to_owned()));
+        assert_eq!(netrc_password("other.com"), Some("def456".to_owned()));
+        assert_eq!(netrc_password("nope.com"), None);
+
+        std::fs::remove_dir_all(&home).ok();
+    }
+
+    #[test]
+    fn gh_cli_token_finds_entry_for_host() {
+        let _guard = HOME_LOCK.lock().unwrap();
+        let home = test_home();
+        std::fs::create_dir_all(home.join(".config/gh")).unwrap();
+        std::fs::write(
+            home.join(".config/gh/hosts.yml"),
+            "github.com:\n    oauth_token: gho_abc123\nghe.example.com:\n    oauth_token: \"gho_def456\"\n",
+        )
+        .unwrap();
+        std::env::set_var("HOME", &home);
+
+        assert_eq!(gh_cli_token("github.com"), Some("gho_abc123".to_owned()));
+        assert_eq!(gh_cli_token("ghe.example.com"), Some("gho_def456".to_owned()));
+        assert_eq!(gh_cli_token("nope.com"), None);
+
+        std::fs::remove_dir_all(&home).ok();
+    }
+
+    #[test]
+    fn resolve_token_prefers_token_command_over_env_var() {
+        let _guard = HOME_LOCK.lock().unwrap();
+        std::env::set_var(GITHUB_TOKEN_ENV, "env-token");
+
+        let block_config = GithubConfig {
+            token_command: Some("echo cmd-token".to_owned()),
+            ..Default::default()
+        };
+
+        assert_eq!(resolve_token(&block_config, "github.com").unwrap(), "cmd-token");
+
+        std::env::remove_var(GITHUB_TOKEN_ENV);
+    }
+
+    #[test]
+    fn resolve_token_falls_back_to_env_var_when_no_file_or_command() {
+        let _guard = HOME_LOCK.lock().unwrap();
+        std::env::set_var("HOME", test_home());
+        std::env::set_var(GITHUB_TOKEN_ENV, "env-token");
+
+        let block_config = GithubConfig::default();
+
+        assert_eq!(resolve_token(&block_config, "github.com").unwrap(), "env-token");
+
+        std::env::remove_var(GITHUB_TOKEN_ENV);
+    }
+
+    #[test]
+    fn resolve_token_errors_naming_every_source_tried() {
+        let _guard = HOME_LOCK.lock().unwrap();
+        std::env::set_var("HOME", test_home());
+        std::env::remove_var(GITHUB_TOKEN_ENV);
+
+        let block_config = GithubConfig::default();
+        let err = resolve_token(&block_config, "github.com").unwrap_err();
+
+        assert!(err.1.contains(GITHUB_TOKEN_ENV));
+        assert!(err.1.contains("netrc"));
+        assert!(err.1.contains("hosts.yml"));
+    }
+}